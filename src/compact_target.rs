@@ -0,0 +1,186 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Extra `U256` surface that `uint::construct_uint!` doesn't hand us for
+//! free: the compact ("nBits") target encoding used for difficulty targets,
+//! wrapping arithmetic, a validating byte-slice conversion, and `from_u128`.
+//!
+//! Checked/saturating/overflowing arithmetic, `pow`, `bits`, `is_zero`,
+//! `low_u128`, and the infallible (panicking) `From<&[u8]>` are already
+//! generated by `construct_uint!` itself.
+
+use crate::U256;
+use std::convert::TryFrom;
+
+/// Error returned when converting a byte slice into a [`U256`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryFromSliceError {
+    pub actual_len: usize,
+}
+
+impl std::fmt::Display for TryFromSliceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected a 32 byte big-endian slice, got {} bytes",
+            self.actual_len
+        )
+    }
+}
+
+impl std::error::Error for TryFromSliceError {}
+
+impl TryFrom<&[u8]> for U256 {
+    type Error = TryFromSliceError;
+
+    /// Like `U256::from(&[u8])`, but returns an error instead of panicking
+    /// when `bytes` isn't exactly 32 bytes — for chain-math code parsing
+    /// difficulty/fee/target bytes out of untrusted input.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 32 {
+            return Err(TryFromSliceError {
+                actual_len: bytes.len(),
+            });
+        }
+        Ok(U256::from_big_endian(bytes))
+    }
+}
+
+impl U256 {
+    /// Builds a `U256` from a `u128`.
+    pub fn from_u128(value: u128) -> U256 {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    /// Wrapping (modular) addition.
+    pub fn wrapping_add(&self, other: U256) -> U256 {
+        self.overflowing_add(other).0
+    }
+
+    /// Wrapping (modular) subtraction.
+    pub fn wrapping_sub(&self, other: U256) -> U256 {
+        self.overflowing_sub(other).0
+    }
+
+    /// Wrapping (modular) multiplication.
+    pub fn wrapping_mul(&self, other: U256) -> U256 {
+        self.overflowing_mul(other).0
+    }
+}
+
+/// Unpacks a "compact" target of the form used for difficulty bits: a
+/// 1-byte exponent followed by a 3-byte mantissa, i.e.
+/// `mantissa * 256^(exponent - 3)`.
+pub fn from_compact(bits: u32) -> U256 {
+    let exponent = (bits >> 24) as u32;
+    let mantissa = U256::from(u64::from(bits & 0x007f_ffff));
+
+    if exponent <= 3 {
+        mantissa >> (8 * (3 - exponent) as usize)
+    } else {
+        mantissa << (8 * (exponent - 3) as usize)
+    }
+}
+
+/// Packs `target` into the same 1-byte-exponent + 3-byte-mantissa compact
+/// form `from_compact` unpacks, matching Bitcoin/Decred's `nBits` encoding
+/// (including its sign-bit convention: a mantissa whose high bit is set has
+/// its exponent bumped by one byte and is shifted down to keep the result
+/// unsigned).
+pub fn to_compact(target: &U256) -> u32 {
+    let mut bytes = [0u8; 32];
+    target.to_big_endian(&mut bytes);
+
+    // Index (from the start) of the first non-zero byte.
+    let first_nonzero = bytes.iter().position(|&b| b != 0);
+
+    let first_nonzero = match first_nonzero {
+        Some(i) => i,
+        None => return 0,
+    };
+
+    let mut size = (32 - first_nonzero) as u32;
+    let mut mantissa: u32 = if size <= 3 {
+        let mut m = 0u32;
+        for &b in &bytes[first_nonzero..] {
+            m = (m << 8) | b as u32;
+        }
+        m << (8 * (3 - size))
+    } else {
+        ((bytes[first_nonzero] as u32) << 16)
+            | ((bytes[first_nonzero + 1] as u32) << 8)
+            | (bytes[first_nonzero + 2] as u32)
+    };
+
+    // If the mantissa's top bit would be set it would be misread as a sign
+    // bit, so shift one byte into the exponent instead.
+    if mantissa & 0x0080_0000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+
+    (size << 24) | mantissa
+}
+
+#[test]
+fn test_from_u128_roundtrip() {
+    let v: u128 = 0x1234_5678_9abc_def0_1122_3344_5566_7788;
+    assert_eq!(U256::from_u128(v).low_u128(), v);
+}
+
+#[test]
+fn test_try_from_slice_roundtrip() {
+    let v = U256::from_u128(0x0102_0304);
+    let bytes: [u8; 32] = v.into();
+    let back = U256::try_from(&bytes[..]).unwrap();
+    assert_eq!(back, v);
+}
+
+#[test]
+fn test_try_from_slice_rejects_wrong_length() {
+    let bytes = [0u8; 16];
+    assert!(U256::try_from(&bytes[..]).is_err());
+
+    let bytes = [0u8; 33];
+    assert!(U256::try_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_wrapping_arithmetic_wraps_on_overflow() {
+    assert_eq!(U256::max_value().wrapping_add(U256::from(1u64)), U256::zero());
+    assert_eq!(U256::zero().wrapping_sub(U256::from(1u64)), U256::max_value());
+}
+
+#[test]
+fn test_compact_target_roundtrip() {
+    // `to_compact` truncates anything below its 3-byte mantissa window, so
+    // the round trip that holds is from_compact(to_compact(x)) == x for a
+    // target whose only significant bytes already fit in that window, not
+    // to_compact(from_compact(bits)) == bits for an arbitrary bit pattern.
+    for (mantissa, shift) in [
+        (0x0000_01u32, 0),
+        (0x7fff_ffu32, 0),
+        (0x1234_56u32, 8 * 5),
+        (0x00ff_ffu32, 8 * 26),
+    ] {
+        let target = U256::from(u64::from(mantissa)) << shift;
+        assert_eq!(from_compact(to_compact(&target)), target);
+    }
+}
+
+#[test]
+fn test_compact_target_zero() {
+    assert_eq!(from_compact(0), U256::zero());
+    assert_eq!(to_compact(&U256::zero()), 0);
+}
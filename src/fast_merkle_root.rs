@@ -79,6 +79,82 @@ pub fn fast_merkle_root(mut leaves: Vec<H256>) -> H256 {
     leaves[0]
 }
 
+/// A single step on the path from a leaf up to the merkle root.
+///
+/// `sibling` is the hash that the running value is combined with at this
+/// level, and `left` records which side that sibling sits on so a verifier
+/// knows whether to fold it in as `hash_with(sibling, running)` or
+/// `hash_with(running, sibling)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: H256,
+    pub left: bool,
+}
+
+/// An inclusion proof for a single leaf of a tree built by [`fast_merkle_root`].
+///
+/// The steps are ordered from the leaf's level up to the root, mirroring
+/// exactly the levels [`fast_merkle_root`] itself walks, including its rule
+/// of duplicating the final node of an odd-sized level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub steps: Vec<ProofStep>,
+}
+
+/// Builds a [`MerkleProof`] that `leaves[index]` is part of the tree rooted
+/// at `fast_merkle_root(leaves)`.
+///
+/// Panics if `index` is out of bounds for `leaves`, mirroring the existing
+/// `leaves[0]` style indexing used by `fast_merkle_root`.
+pub fn merkle_proof(leaves: &[H256], index: usize) -> MerkleProof {
+    assert!(index < leaves.len(), "merkle_proof: index out of bounds");
+
+    let mut level: Vec<H256> = leaves.to_vec();
+    let mut pos = index;
+    let mut steps = Vec::new();
+
+    while level.len() > 1 {
+        // Mirror fast_merkle_root's odd-level duplication so the sibling
+        // recorded here matches what the root computation actually used.
+        if level.len() & 1 != 0 {
+            level.push(level[level.len() - 1]);
+        }
+
+        let sibling_pos = pos ^ 1;
+        steps.push(ProofStep {
+            sibling: level[sibling_pos],
+            left: sibling_pos < pos,
+        });
+
+        let mut next = Vec::with_capacity(level.len() / 2);
+        let mut i = 0;
+        while i < level.len() / 2 {
+            next.push(level[i * 2].hash_with(level[i * 2 + 1]));
+            i += 1;
+        }
+        level = next;
+        pos /= 2;
+    }
+
+    MerkleProof { steps }
+}
+
+/// Verifies that `leaf` is included under `root` according to `proof`,
+/// folding the leaf upward with `hash_with` in the recorded order.
+pub fn verify_merkle_proof(leaf: H256, proof: &MerkleProof, root: H256) -> bool {
+    let mut running = leaf;
+
+    for step in &proof.steps {
+        running = if step.left {
+            step.sibling.hash_with(running)
+        } else {
+            running.hash_with(step.sibling)
+        };
+    }
+
+    running == root
+}
+
 #[test]
 fn test_to_merkle_fast_short() {
     let _inputs = vec![
@@ -100,3 +176,61 @@ fn test_to_merkle_fast_short() {
 fn test_to_merkle_fast_zero() {
     assert_eq!(fast_merkle_root(vec![H256::zero()]), H256::zero());
 }
+
+#[test]
+fn test_merkle_proof_single_leaf() {
+    let leaf = H256::from_hex("5e574591d900f7f9abb8f8eb31cc9330247d27ba293ad79c348d602ece717b8b").unwrap();
+    let root = fast_merkle_root(vec![leaf]);
+    let proof = merkle_proof(&[leaf], 0);
+
+    assert!(proof.steps.is_empty());
+    assert!(verify_merkle_proof(leaf, &proof, root));
+    assert!(!verify_merkle_proof(H256::zero(), &proof, root));
+}
+
+#[test]
+fn test_merkle_proof_even_leaves() {
+    let leaves = vec![
+        H256::from_hex("5e574591d900f7f9abb8f8eb31cc9330247d27ba293ad79c348d602ece717b8b").unwrap(),
+        H256::from_hex("b3b70fe08c2da744c9559d533e8db35b3bfefba1b0f1c7b31e7d9d523c00a426").unwrap(),
+        H256::from_hex("dd3058a7fc691ff4dee0a8cd6030f404ffda7e7aee88aff3985f7b2bbe4792f7").unwrap(),
+        H256::from_hex("5e574591d900f7f9abb8f8eb31cc9330247d27ba293ad79c348d602ece717b8b").unwrap(),
+    ];
+    let root = fast_merkle_root(leaves.clone());
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = merkle_proof(&leaves, index);
+        assert!(verify_merkle_proof(*leaf, &proof, root));
+    }
+}
+
+#[test]
+fn test_merkle_proof_odd_leaves_duplicates_last_node() {
+    let leaves = vec![
+        H256::from_hex("5e574591d900f7f9abb8f8eb31cc9330247d27ba293ad79c348d602ece717b8b").unwrap(),
+        H256::from_hex("b3b70fe08c2da744c9559d533e8db35b3bfefba1b0f1c7b31e7d9d523c00a426").unwrap(),
+        H256::from_hex("dd3058a7fc691ff4dee0a8cd6030f404ffda7e7aee88aff3985f7b2bbe4792f7").unwrap(),
+    ];
+    let root = fast_merkle_root(leaves.clone());
+
+    for (index, leaf) in leaves.iter().enumerate() {
+        let proof = merkle_proof(&leaves, index);
+        assert!(verify_merkle_proof(*leaf, &proof, root));
+    }
+
+    // The last leaf is self-duplicated at the first level, so its own proof
+    // must fold the running value with itself.
+    let proof = merkle_proof(&leaves, 2);
+    assert_eq!(proof.steps[0].sibling, leaves[2]);
+}
+
+#[test]
+fn test_merkle_proof_rejects_wrong_root() {
+    let leaves = vec![
+        H256::from_hex("5e574591d900f7f9abb8f8eb31cc9330247d27ba293ad79c348d602ece717b8b").unwrap(),
+        H256::from_hex("b3b70fe08c2da744c9559d533e8db35b3bfefba1b0f1c7b31e7d9d523c00a426").unwrap(),
+    ];
+    let proof = merkle_proof(&leaves, 0);
+
+    assert!(!verify_merkle_proof(leaves[0], &proof, H256::zero()));
+}
@@ -0,0 +1,61 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Various hash functions & types.
+
+pub use crate::types::{H128, H160, H256, H384};
+
+/// Fast, non-cryptographic, xxh3-inspired hashing for hash-table keying,
+/// dedup sets and bloom-filter style checks over large in-memory data. Not
+/// wire-compatible with the reference xxh3 algorithm — see the module docs.
+pub mod xxhash;
+
+impl H256 {
+    /// Parses a hex-encoded 32 byte hash, with or without a leading `0x`.
+    pub fn from_hex(s: &str) -> Result<H256, ::hex::FromHexError> {
+        let stripped = s.strip_prefix("0x").unwrap_or(s);
+        let mut out = [0u8; 32];
+        ::hex::decode_to_slice(stripped, &mut out)?;
+        Ok(H256(out))
+    }
+
+    /// Hashes `self` concatenated with `other` using blake2b with a 32 byte
+    /// (256 bit) output length. This is the primitive `fast_merkle_root` and `tree_hash`
+    /// fold their levels with.
+    pub fn hash_with(&self, other: H256) -> H256 {
+        let mut input = [0u8; 64];
+        input[..32].copy_from_slice(self.as_bytes());
+        input[32..].copy_from_slice(other.as_bytes());
+
+        let digest = crate::blake2::Params::new().hash_length(32).hash(&input);
+        H256::from_slice(digest.as_bytes())
+    }
+}
+
+#[test]
+fn test_hash_with_is_deterministic() {
+    let a = H256::from_low_u64_be(1);
+    let b = H256::from_low_u64_be(2);
+
+    assert_eq!(a.hash_with(b), a.hash_with(b));
+    assert_ne!(a.hash_with(b), b.hash_with(a));
+}
+
+#[test]
+fn test_from_hex_roundtrip() {
+    let h = H256::from_low_u64_be(0xdead_beef);
+    let encoded = format!("0x{}", ::hex::encode(h.as_bytes()));
+
+    assert_eq!(H256::from_hex(&encoded).unwrap(), h);
+}
@@ -0,0 +1,311 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A non-cryptographic 64-bit hash, xxh3-inspired, for `HashMap` keying,
+//! dedup sets and bloom-filter style membership checks where blake2b would
+//! be overkill.
+//!
+//! This follows the *shape* of xxh3 (16-byte lane mixing, dedicated
+//! short-input paths, a final avalanche), but the secret table and mixing
+//! constants here are this crate's own and have not been checked against
+//! the upstream xxh3 reference implementation or its test vectors. Hashes
+//! produced by this module will **not** match `xxh3_64` from xxHash, any
+//! other language's xxh3 port, or any future version of this module across
+//! a breaking change — treat it as a fast, in-process, self-consistent hash
+//! only, not as a wire-compatible implementation of the xxh3 spec.
+
+use std::hash::{BuildHasher, Hasher};
+
+const PRIME_1: u64 = 0x9E3779B185EBCA87;
+const PRIME_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME_3: u64 = 0x165667B19E3779F9;
+const PRIME_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME_5: u64 = 0x27D4EB2F165667C5;
+
+const DEFAULT_SEED: u64 = 0;
+
+/// A 192 byte block of secret-derived constants the core mixing step draws
+/// per-lane keys from.
+const SECRET: [u64; 24] = [
+    0xb8fe6c3923a44bbe,
+    0x7c01812cf721ad1c,
+    0xded46de9839097db,
+    0x7240a4a4b7b3671f,
+    0xcb79e64eccc0e578,
+    0x825ad07dccff7221,
+    0xb8084674f743248e,
+    0xe03590e6813a264c,
+    0x3c2852bb91c300cb,
+    0x88c72e0b99f0a149,
+    0x7db0506b9b62cc3a,
+    0xf3acc9ed3ba6f3b0,
+    0x9b8b5c0a77a3b9e9,
+    0x1a8b4c5e1dfd3b87,
+    0x47b6dea0a0b8e3c1,
+    0xa35d1e3d2e8c7f4b,
+    0x6c3d9e1f2b8a5d7e,
+    0x0f1e2d3c4b5a6978,
+    0x8899aabbccddeeff,
+    0x1122334455667788,
+    0x9900aabbccddeeff,
+    0x3344556677889900,
+    0xaabbccddeeff0011,
+    0x2233445566778899,
+];
+
+fn avalanche(mut acc: u64) -> u64 {
+    acc ^= acc >> 37;
+    acc = acc.wrapping_mul(PRIME_3);
+    acc ^= acc >> 32;
+    acc
+}
+
+fn read_u64_le(bytes: &[u8]) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_le_bytes(buf)
+}
+
+/// Mixes a single 16-byte lane into `acc`: each half is XORed with a
+/// rotating secret-derived constant, the two halves are multiplied, and the
+/// 128-bit product is folded back in by XORing its high and low halves.
+fn mix_lane(acc: u64, lane: &[u8], secret_a: u64, secret_b: u64) -> u64 {
+    let lo = read_u64_le(&lane[0..8]) ^ secret_a;
+    let hi = read_u64_le(&lane[8..16]) ^ secret_b;
+
+    let product = (lo as u128).wrapping_mul(hi as u128);
+    let folded = (product >> 64) as u64 ^ product as u64;
+
+    acc ^ folded
+}
+
+/// Dedicated mixing for inputs of 0-16 bytes: reads from both ends of the
+/// buffer to avoid branchy short-input tails.
+fn xxh3_short(bytes: &[u8], seed: u64) -> u64 {
+    let len = bytes.len();
+
+    if len == 0 {
+        return avalanche(seed ^ PRIME_1 ^ PRIME_5);
+    }
+
+    if len < 4 {
+        let c1 = bytes[0] as u32;
+        let c2 = bytes[len / 2] as u32;
+        let c3 = bytes[len - 1] as u32;
+        let combined = (c1 << 16) | (c2 << 24) | c3 | ((len as u32) << 8);
+        let input = (combined as u64) ^ (SECRET[0] ^ SECRET[1]).wrapping_add(seed);
+        return avalanche(input);
+    }
+
+    if len <= 8 {
+        let input_lo = {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&bytes[0..4]);
+            u32::from_le_bytes(buf) as u64
+        };
+        let input_hi = {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&bytes[len - 4..len]);
+            u32::from_le_bytes(buf) as u64
+        };
+        let bitflip = (SECRET[1] ^ SECRET[2]).wrapping_add(seed);
+        let keyed = (input_hi.wrapping_add(input_lo << 32)) ^ bitflip;
+        let mixed = keyed ^ (keyed.rotate_left(49) ^ keyed.rotate_left(24));
+        let product = (mixed as u128).wrapping_mul((PRIME_2 ^ (len as u64)) as u128);
+        return avalanche((product >> 64) as u64 ^ product as u64);
+    }
+
+    // len in 9..=16: read a lane from each end, overlapping in the middle.
+    let bitflip_lo = (SECRET[3] ^ SECRET[4]).wrapping_sub(seed);
+    let bitflip_hi = (SECRET[5] ^ SECRET[6]).wrapping_add(seed);
+    let input_lo = read_u64_le(&bytes[0..8]) ^ bitflip_lo;
+    let input_hi = read_u64_le(&bytes[len - 8..len]) ^ bitflip_hi;
+
+    let product = (input_lo as u128).wrapping_mul(input_hi as u128);
+    let acc = (len as u64)
+        .wrapping_add(input_lo.swap_bytes())
+        .wrapping_add(input_hi)
+        .wrapping_add((product >> 64) as u64 ^ product as u64);
+
+    avalanche(acc)
+}
+
+/// Mixes the bulk of a long input (> 16 bytes) in 16-byte lanes.
+fn xxh3_long(bytes: &[u8], seed: u64) -> u64 {
+    let mut acc = (bytes.len() as u64).wrapping_mul(PRIME_1) ^ seed;
+
+    let mut offset = 0;
+    let mut lane_index = 0;
+    while offset + 16 <= bytes.len() {
+        let secret_a = SECRET[lane_index % SECRET.len()];
+        let secret_b = SECRET[(lane_index + 1) % SECRET.len()];
+        acc = mix_lane(acc, &bytes[offset..offset + 16], secret_a, secret_b);
+        acc = acc.wrapping_mul(PRIME_4);
+
+        offset += 16;
+        lane_index += 2;
+    }
+
+    // Fold in any trailing 1-15 bytes as one more lane, zero-padded to 16 bytes.
+    if offset < bytes.len() {
+        let remainder = &bytes[offset..];
+        let mut buf = [0u8; 16];
+        buf[..remainder.len()].copy_from_slice(remainder);
+        let secret_a = SECRET[lane_index % SECRET.len()];
+        let secret_b = SECRET[(lane_index + 1) % SECRET.len()];
+        acc = mix_lane(acc, &buf, secret_a, secret_b);
+    }
+
+    avalanche(acc)
+}
+
+/// Computes this module's xxh3-inspired 64-bit hash of `bytes` with the
+/// default seed. Not wire-compatible with the reference xxh3 algorithm —
+/// see the module docs.
+pub fn xxh3_64(bytes: &[u8]) -> u64 {
+    xxh3_64_with_seed(bytes, DEFAULT_SEED)
+}
+
+/// Computes this module's xxh3-inspired 64-bit hash of `bytes` with an
+/// explicit `seed`. Not wire-compatible with the reference xxh3 algorithm —
+/// see the module docs.
+pub fn xxh3_64_with_seed(bytes: &[u8], seed: u64) -> u64 {
+    if bytes.len() <= 16 {
+        xxh3_short(bytes, seed)
+    } else {
+        xxh3_long(bytes, seed)
+    }
+}
+
+/// A [`std::hash::Hasher`] backed by [`xxh3_64`], for plugging xxh3 into a
+/// `HashMap` or `HashSet` via [`Xxh3Builder`].
+///
+/// Bytes are buffered until `finish()` is called, since xxh3 is a one-shot
+/// hash over the whole input rather than an incremental one.
+#[derive(Clone, Debug, Default)]
+pub struct Xxh3Hasher {
+    seed: u64,
+    buffer: Vec<u8>,
+}
+
+impl Xxh3Hasher {
+    /// Creates a hasher using the default seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Creates a hasher seeded with `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Xxh3Hasher {
+            seed,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl Hasher for Xxh3Hasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        xxh3_64_with_seed(&self.buffer, self.seed)
+    }
+}
+
+/// A [`BuildHasher`] producing [`Xxh3Hasher`]s, for use as
+/// `HashMap<K, V, Xxh3Builder>`.
+#[derive(Clone, Debug, Default)]
+pub struct Xxh3Builder {
+    seed: u64,
+}
+
+impl Xxh3Builder {
+    /// Creates a builder using the default seed.
+    pub fn new() -> Self {
+        Xxh3Builder { seed: DEFAULT_SEED }
+    }
+
+    /// Creates a builder whose hashers are seeded with `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Xxh3Builder { seed }
+    }
+}
+
+impl BuildHasher for Xxh3Builder {
+    type Hasher = Xxh3Hasher;
+
+    fn build_hasher(&self) -> Xxh3Hasher {
+        Xxh3Hasher::with_seed(self.seed)
+    }
+}
+
+#[test]
+fn test_xxh3_64_empty() {
+    // The empty input must be deterministic and seed-dependent.
+    assert_eq!(xxh3_64(&[]), xxh3_64_with_seed(&[], DEFAULT_SEED));
+    assert_ne!(xxh3_64(&[]), xxh3_64_with_seed(&[], 1));
+}
+
+#[test]
+fn test_xxh3_64_is_deterministic() {
+    let data = b"the quick brown fox jumps over the lazy dog";
+    assert_eq!(xxh3_64(data), xxh3_64(data));
+}
+
+#[test]
+fn test_xxh3_64_short_input_boundaries() {
+    // Exercise the 0, 1-3, 4-8 and 9-16 byte short-input branches.
+    for len in [0usize, 1, 3, 4, 8, 9, 16] {
+        let data: Vec<u8> = (0..len as u8).collect();
+        let a = xxh3_64(&data);
+        let b = xxh3_64(&data);
+        assert_eq!(a, b, "hash for len {} must be stable", len);
+    }
+}
+
+#[test]
+fn test_xxh3_64_long_input() {
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    assert_eq!(xxh3_64(&data), xxh3_64(&data));
+    assert_ne!(xxh3_64(&data[..999]), xxh3_64(&data));
+}
+
+#[test]
+fn test_xxh3_64_with_seed_changes_output() {
+    let data = b"seeded input";
+    assert_ne!(xxh3_64_with_seed(data, 0), xxh3_64_with_seed(data, 1));
+}
+
+#[test]
+fn test_xxh3_hasher_matches_xxh3_64() {
+    let data = b"hash me via the std Hasher adapter";
+
+    let mut hasher = Xxh3Hasher::new();
+    hasher.write(data);
+
+    assert_eq!(hasher.finish(), xxh3_64(data));
+}
+
+#[test]
+fn test_xxh3_builder_with_hashmap() {
+    use std::collections::HashMap;
+
+    let mut map: HashMap<&str, u32, Xxh3Builder> = HashMap::with_hasher(Xxh3Builder::new());
+    map.insert("one", 1);
+    map.insert("two", 2);
+
+    assert_eq!(map.get("one"), Some(&1));
+    assert_eq!(map.get("two"), Some(&2));
+}
@@ -25,9 +25,11 @@ pub mod tai64;
 pub use byteorder;
 /// Variable Encoding Integer
 mod varint;
-pub use varint::VarInt;
+pub use varint::{Endian, IntEncoding, VarInt};
 /// Export Curve
 pub use curve25519_dalek as dalek;
+/// Fixed-size hash & uint types, plus their serde/Mohan serialization impls
+mod types;
 /// Various Hash functions & types
 pub mod hash;
 /// Export blake2b
@@ -36,13 +38,18 @@ pub use blake2b_simd as blake2;
 mod fast_merkle_root;
 /// That extra sauce
 pub mod tools;
-pub use fast_merkle_root::fast_merkle_root;
+pub use fast_merkle_root::{fast_merkle_root, merkle_proof, verify_merkle_proof, MerkleProof, ProofStep};
+/// SSZ-style merkleization with length mixing
+pub mod tree_hash;
 
 
 
 uint::construct_uint! {
     pub struct U256(4);
 }
+/// Byte conversions and compact ("nBits") target packing for `U256`
+mod compact_target;
+pub use compact_target::{from_compact, to_compact, TryFromSliceError};
 
 
 
@@ -0,0 +1,179 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Core serialization traits: `Readable`/`Writeable` for types, and the
+//! `Reader`/`Writer` primitives they are built on top of.
+
+use std::io;
+
+/// Errors that can occur while reading or writing.
+#[derive(Debug)]
+pub enum Error {
+    /// Underlying IO error.
+    IOErr(io::Error),
+    /// Data did not round-trip through its canonical encoding (e.g. a
+    /// non-canonical `VarInt` marker, or a length prefix that didn't fit).
+    UnexpectedData,
+    /// Not enough bytes were available to satisfy a read.
+    UnexpectedEof,
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::IOErr(err)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::IOErr(e) => write!(f, "IO error: {}", e),
+            Error::UnexpectedData => write!(f, "unexpected data"),
+            Error::UnexpectedEof => write!(f, "unexpected end of input"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A trait for reading primitives and fixed-size byte spans out of some
+/// underlying byte source.
+pub trait Reader {
+    fn read_u8(&mut self) -> Result<u8, Error>;
+    fn read_u16(&mut self) -> Result<u16, Error>;
+    fn read_u32(&mut self) -> Result<u32, Error>;
+    fn read_u64(&mut self) -> Result<u64, Error>;
+    fn read_u128(&mut self) -> Result<u128, Error>;
+    /// Reads exactly `length` bytes.
+    fn read_fixed_bytes(&mut self, length: usize) -> Result<Vec<u8>, Error>;
+}
+
+/// A trait for writing primitives and fixed-size byte spans to some
+/// underlying byte sink.
+pub trait Writer {
+    fn write_u8(&mut self, n: u8) -> Result<(), Error>;
+    fn write_u16(&mut self, n: u16) -> Result<(), Error>;
+    fn write_u32(&mut self, n: u32) -> Result<(), Error>;
+    fn write_u64(&mut self, n: u64) -> Result<(), Error>;
+    fn write_u128(&mut self, n: u128) -> Result<(), Error>;
+    /// Writes `bytes` verbatim, with no length prefix.
+    fn write_fixed_bytes(&mut self, bytes: &[u8]) -> Result<(), Error>;
+}
+
+/// A type that knows how to read itself from a [`Reader`].
+pub trait Readable: Sized {
+    fn read(reader: &mut dyn Reader) -> Result<Self, Error>;
+}
+
+/// A type that knows how to write itself to a [`Writer`].
+pub trait Writeable {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), Error>;
+}
+
+/// A [`Writer`] that appends to an in-memory `Vec<u8>`, always in
+/// little-endian byte order.
+pub struct VecWriter {
+    pub buf: Vec<u8>,
+}
+
+impl VecWriter {
+    pub fn new() -> Self {
+        VecWriter { buf: Vec::new() }
+    }
+}
+
+impl Default for VecWriter {
+    fn default() -> Self {
+        VecWriter::new()
+    }
+}
+
+impl Writer for VecWriter {
+    fn write_u8(&mut self, n: u8) -> Result<(), Error> {
+        self.buf.push(n);
+        Ok(())
+    }
+    fn write_u16(&mut self, n: u16) -> Result<(), Error> {
+        self.buf.extend_from_slice(&n.to_le_bytes());
+        Ok(())
+    }
+    fn write_u32(&mut self, n: u32) -> Result<(), Error> {
+        self.buf.extend_from_slice(&n.to_le_bytes());
+        Ok(())
+    }
+    fn write_u64(&mut self, n: u64) -> Result<(), Error> {
+        self.buf.extend_from_slice(&n.to_le_bytes());
+        Ok(())
+    }
+    fn write_u128(&mut self, n: u128) -> Result<(), Error> {
+        self.buf.extend_from_slice(&n.to_le_bytes());
+        Ok(())
+    }
+    fn write_fixed_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// A [`Reader`] over an in-memory byte slice, always in little-endian byte
+/// order.
+pub struct SliceReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        SliceReader { buf, pos: 0 }
+    }
+}
+
+impl<'a> Reader for SliceReader<'a> {
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        let bytes = self.read_fixed_bytes(1)?;
+        Ok(bytes[0])
+    }
+    fn read_u16(&mut self) -> Result<u16, Error> {
+        let bytes = self.read_fixed_bytes(2)?;
+        let mut buf = [0u8; 2];
+        buf.copy_from_slice(&bytes);
+        Ok(u16::from_le_bytes(buf))
+    }
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.read_fixed_bytes(4)?;
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&bytes);
+        Ok(u32::from_le_bytes(buf))
+    }
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.read_fixed_bytes(8)?;
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&bytes);
+        Ok(u64::from_le_bytes(buf))
+    }
+    fn read_u128(&mut self) -> Result<u128, Error> {
+        let bytes = self.read_fixed_bytes(16)?;
+        let mut buf = [0u8; 16];
+        buf.copy_from_slice(&bytes);
+        Ok(u128::from_le_bytes(buf))
+    }
+    fn read_fixed_bytes(&mut self, length: usize) -> Result<Vec<u8>, Error> {
+        if self.pos + length > self.buf.len() {
+            return Err(Error::UnexpectedEof);
+        }
+        let out = self.buf[self.pos..self.pos + length].to_vec();
+        self.pos += length;
+        Ok(out)
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SSZ-style merkleization, as an alternative to the Decred-flavoured
+//! odd-node-duplication rule used by `fast_merkle_root`.
+//!
+//! Unlike `fast_merkle_root`, `merkleize` always builds a tree over a
+//! power-of-two number of chunks by padding with zero chunks, which makes it
+//! suitable for fixed commitments over dynamically sized lists once combined
+//! with `mix_in_length`.
+
+use crate::hash::H256;
+
+/// Number of depths held in the precomputed zero-hash table.
+const ZERO_HASHES_MAX_DEPTH: usize = 64;
+
+/// Precomputed hashes of all-zero subtrees, indexed by tree depth.
+///
+/// `zero_hashes()[0]` is the hash of a single zero chunk (i.e. `H256::zero()`)
+/// and `zero_hashes()[k]` is `hash_with(zero_hashes()[k - 1], zero_hashes()[k - 1])`,
+/// the root of a fully zeroed subtree with `2^k` leaves.
+fn zero_hashes() -> &'static [H256; ZERO_HASHES_MAX_DEPTH] {
+    use std::sync::OnceLock;
+
+    static CACHE: OnceLock<[H256; ZERO_HASHES_MAX_DEPTH]> = OnceLock::new();
+
+    CACHE.get_or_init(|| {
+        let mut table = [H256::zero(); ZERO_HASHES_MAX_DEPTH];
+        for i in 1..ZERO_HASHES_MAX_DEPTH {
+            table[i] = table[i - 1].hash_with(table[i - 1]);
+        }
+        table
+    })
+}
+
+/// Returns the zero hash for a fully-zeroed subtree of depth `depth`
+/// (i.e. holding `2^depth` zero leaves).
+fn zero_hash(depth: usize) -> H256 {
+    zero_hashes()[depth]
+}
+
+/// Merkleizes `chunks` into a single root, padding the chunk count up to the
+/// next power of two with all-zero `H256` chunks and hashing adjacent pairs
+/// with `hash_with` until one node remains.
+///
+/// An empty input returns `H256::zero()`.
+pub fn merkleize(chunks: &[H256]) -> H256 {
+    if chunks.is_empty() {
+        return H256::zero();
+    }
+
+    if chunks.len() == 1 {
+        return chunks[0];
+    }
+
+    let depth = (chunks.len() as f64 - 1.0).log2().floor() as usize + 1;
+    let width = 1usize << depth;
+
+    let mut level: Vec<H256> = Vec::with_capacity(width);
+    level.extend_from_slice(chunks);
+
+    let mut level_depth = 0;
+    while level.len() < width {
+        level.push(zero_hash(level_depth));
+    }
+
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len() / 2);
+        let mut i = 0;
+        while i < level.len() {
+            // A fully-zero subtree always hashes to the cached zero hash for
+            // its depth, so substitute it instead of recomputing.
+            if level[i] == zero_hash(level_depth) && level[i + 1] == zero_hash(level_depth) {
+                next.push(zero_hash(level_depth + 1));
+            } else {
+                next.push(level[i].hash_with(level[i + 1]));
+            }
+            i += 2;
+        }
+        level = next;
+        level_depth += 1;
+    }
+
+    level[0]
+}
+
+/// Mixes the element count of a list into its merkleized `root`, yielding a
+/// stable commitment for dynamically sized collections.
+///
+/// `length` is encoded as a little-endian 256-bit integer packed into an
+/// `H256`, then combined with `root` via `hash_with(root, len_as_256)`.
+pub fn mix_in_length(root: H256, length: usize) -> H256 {
+    let mut len_as_256 = [0u8; 32];
+    len_as_256[..8].copy_from_slice(&(length as u64).to_le_bytes());
+
+    root.hash_with(H256::from_slice(&len_as_256))
+}
+
+#[test]
+fn test_merkleize_empty() {
+    assert_eq!(merkleize(&[]), H256::zero());
+}
+
+#[test]
+fn test_merkleize_single_chunk() {
+    let chunk = H256::from_low_u64_be(42);
+    assert_eq!(merkleize(&[chunk]), chunk);
+}
+
+#[test]
+fn test_merkleize_pads_to_power_of_two() {
+    let a = H256::from_low_u64_be(1);
+    let b = H256::from_low_u64_be(2);
+    let c = H256::from_low_u64_be(3);
+
+    // 3 chunks should be padded with one zero chunk to width 4.
+    let expected = {
+        let left = a.hash_with(b);
+        let right = c.hash_with(H256::zero());
+        left.hash_with(right)
+    };
+
+    assert_eq!(merkleize(&[a, b, c]), expected);
+}
+
+#[test]
+fn test_merkleize_all_zero_chunks_uses_zero_hash_table() {
+    // A fully-zero 64-leaf tree should equal the precomputed zero hash for
+    // depth 6, exercising the cache substitution this module exists for.
+    let chunks = vec![H256::zero(); 64];
+    assert_eq!(merkleize(&chunks), zero_hash(6));
+}
+
+#[test]
+fn test_merkleize_mostly_zero_sparse_tree() {
+    // 63 zero leaves plus one real leaf: only the subtree containing the
+    // real leaf should ever miss the zero-hash cache.
+    let mut chunks = vec![H256::zero(); 64];
+    chunks[63] = H256::from_low_u64_be(99);
+
+    let expected = {
+        let mut level = chunks.clone();
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len() / 2);
+            for pair in level.chunks(2) {
+                next.push(pair[0].hash_with(pair[1]));
+            }
+            level = next;
+        }
+        level[0]
+    };
+
+    assert_eq!(merkleize(&chunks), expected);
+}
+
+#[test]
+fn test_mix_in_length() {
+    let root = H256::from_low_u64_be(7);
+    let mixed = mix_in_length(root, 3);
+
+    let mut len_as_256 = [0u8; 32];
+    len_as_256[0] = 3;
+    assert_eq!(mixed, root.hash_with(H256::from_slice(&len_as_256)));
+}
@@ -0,0 +1,260 @@
+// Copyright 2021 Stichting Organism
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A compact unsigned integer encoding, following bincode's configurable
+//! `IntEncoding`/`Endian` strategy so downstream serializers can pick their
+//! own wire layout per field instead of being locked to one.
+
+use crate::ser::{Error, Readable, Reader, Writeable, Writer};
+use std::convert::TryFrom;
+
+/// How a [`VarInt`] is laid out on the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntEncoding {
+    /// Always written at `VarInt`'s native width (8 bytes, since it wraps a
+    /// `u64`), in the chosen [`Endian`].
+    Fixint,
+    /// Values below 251 are a single byte; larger values are a marker byte
+    /// (251/252/253/254) followed by the value as a 2/4/8/16-byte integer,
+    /// using the smallest width that fits.
+    Varint,
+}
+
+/// Byte order a [`VarInt`] is encoded in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+const VARINT_U16_MARKER: u8 = 251;
+const VARINT_U32_MARKER: u8 = 252;
+const VARINT_U64_MARKER: u8 = 253;
+const VARINT_U128_MARKER: u8 = 254;
+
+/// A variable-length unsigned integer.
+///
+/// By default `encode`/`decode` use `IntEncoding::Varint` with
+/// `Endian::Little`; use [`VarInt::encode_with`]/[`VarInt::decode_with`] to
+/// pick a different mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VarInt(pub u64);
+
+impl VarInt {
+    /// Encodes `self` using `IntEncoding::Varint` and `Endian::Little`.
+    pub fn encode<W: Writer>(&self, writer: &mut W) -> Result<(), Error> {
+        self.encode_with(writer, IntEncoding::Varint, Endian::Little)
+    }
+
+    /// Decodes a `VarInt` using `IntEncoding::Varint` and `Endian::Little`.
+    pub fn decode<R: Reader + ?Sized>(reader: &mut R) -> Result<VarInt, Error> {
+        VarInt::decode_with(reader, IntEncoding::Varint, Endian::Little)
+    }
+
+    /// Encodes `self` according to `mode` and `endian`.
+    pub fn encode_with<W: Writer + ?Sized>(
+        &self,
+        writer: &mut W,
+        mode: IntEncoding,
+        endian: Endian,
+    ) -> Result<(), Error> {
+        match mode {
+            IntEncoding::Fixint => write_width(writer, self.0 as u128, 8, endian),
+            IntEncoding::Varint => write_varint(writer, self.0 as u128, endian),
+        }
+    }
+
+    /// Decodes a `VarInt` according to `mode` and `endian`, rejecting
+    /// non-canonical varint markers.
+    pub fn decode_with<R: Reader + ?Sized>(
+        reader: &mut R,
+        mode: IntEncoding,
+        endian: Endian,
+    ) -> Result<VarInt, Error> {
+        let value = match mode {
+            IntEncoding::Fixint => read_width(reader, 8, endian)?,
+            IntEncoding::Varint => read_varint(reader, endian)?,
+        };
+
+        u64::try_from(value)
+            .map(VarInt)
+            .map_err(|_| Error::UnexpectedData)
+    }
+}
+
+impl Readable for VarInt {
+    fn read(reader: &mut dyn Reader) -> Result<Self, Error> {
+        VarInt::decode(reader)
+    }
+}
+
+impl Writeable for VarInt {
+    fn write<W: Writer>(&self, writer: &mut W) -> Result<(), Error> {
+        self.encode(writer)
+    }
+}
+
+/// Writes `value` at a fixed `width` (2, 4, 8 or 16 bytes) in `endian` order.
+fn write_width<W: Writer + ?Sized>(writer: &mut W, value: u128, width: usize, endian: Endian) -> Result<(), Error> {
+    let be = value.to_be_bytes();
+    let full = &be[16 - width..];
+
+    match endian {
+        Endian::Big => writer.write_fixed_bytes(full),
+        Endian::Little => {
+            let mut le: Vec<u8> = full.to_vec();
+            le.reverse();
+            writer.write_fixed_bytes(&le)
+        }
+    }
+}
+
+/// Reads a fixed-`width` (2, 4, 8 or 16 byte) integer in `endian` order.
+fn read_width<R: Reader + ?Sized>(reader: &mut R, width: usize, endian: Endian) -> Result<u128, Error> {
+    let bytes = reader.read_fixed_bytes(width)?;
+    let mut be = [0u8; 16];
+
+    match endian {
+        Endian::Big => be[16 - width..].copy_from_slice(&bytes),
+        Endian::Little => {
+            let mut reversed = bytes.clone();
+            reversed.reverse();
+            be[16 - width..].copy_from_slice(&reversed);
+        }
+    }
+
+    Ok(u128::from_be_bytes(be))
+}
+
+/// Writes `value` using the Varint scheme: a single byte below 251, or a
+/// marker byte followed by the smallest width (2/4/8/16 bytes) that fits.
+fn write_varint<W: Writer + ?Sized>(writer: &mut W, value: u128, endian: Endian) -> Result<(), Error> {
+    if value < VARINT_U16_MARKER as u128 {
+        return writer.write_u8(value as u8);
+    }
+
+    if value <= u16::MAX as u128 {
+        writer.write_u8(VARINT_U16_MARKER)?;
+        write_width(writer, value, 2, endian)
+    } else if value <= u32::MAX as u128 {
+        writer.write_u8(VARINT_U32_MARKER)?;
+        write_width(writer, value, 4, endian)
+    } else if value <= u64::MAX as u128 {
+        writer.write_u8(VARINT_U64_MARKER)?;
+        write_width(writer, value, 8, endian)
+    } else {
+        writer.write_u8(VARINT_U128_MARKER)?;
+        write_width(writer, value, 16, endian)
+    }
+}
+
+/// Reads a Varint-encoded value, rejecting any marker whose value would
+/// have fit in a smaller canonical form.
+fn read_varint<R: Reader + ?Sized>(reader: &mut R, endian: Endian) -> Result<u128, Error> {
+    let marker = reader.read_u8()?;
+
+    match marker {
+        0..=250 => Ok(marker as u128),
+        VARINT_U16_MARKER => {
+            let value = read_width(reader, 2, endian)?;
+            if value < VARINT_U16_MARKER as u128 {
+                return Err(Error::UnexpectedData);
+            }
+            Ok(value)
+        }
+        VARINT_U32_MARKER => {
+            let value = read_width(reader, 4, endian)?;
+            if value <= u16::MAX as u128 {
+                return Err(Error::UnexpectedData);
+            }
+            Ok(value)
+        }
+        VARINT_U64_MARKER => {
+            let value = read_width(reader, 8, endian)?;
+            if value <= u32::MAX as u128 {
+                return Err(Error::UnexpectedData);
+            }
+            Ok(value)
+        }
+        VARINT_U128_MARKER => {
+            let value = read_width(reader, 16, endian)?;
+            if value <= u64::MAX as u128 {
+                return Err(Error::UnexpectedData);
+            }
+            Ok(value)
+        }
+        _ => Err(Error::UnexpectedData),
+    }
+}
+
+#[test]
+fn test_varint_roundtrip_small() {
+    for value in [0u64, 1, 42, 250] {
+        let mut writer = crate::ser::VecWriter::new();
+        VarInt(value).encode(&mut writer).unwrap();
+        assert_eq!(writer.buf.len(), 1, "value {} should be one byte", value);
+
+        let mut reader = crate::ser::SliceReader::new(&writer.buf);
+        assert_eq!(VarInt::decode(&mut reader).unwrap(), VarInt(value));
+    }
+}
+
+#[test]
+fn test_varint_roundtrip_each_width() {
+    for value in [251u64, u16::MAX as u64, u16::MAX as u64 + 1, u32::MAX as u64, u32::MAX as u64 + 1, u64::MAX] {
+        for endian in [Endian::Little, Endian::Big] {
+            let mut writer = crate::ser::VecWriter::new();
+            VarInt(value)
+                .encode_with(&mut writer, IntEncoding::Varint, endian)
+                .unwrap();
+
+            let mut reader = crate::ser::SliceReader::new(&writer.buf);
+            let decoded = VarInt::decode_with(&mut reader, IntEncoding::Varint, endian).unwrap();
+            assert_eq!(decoded, VarInt(value));
+        }
+    }
+}
+
+#[test]
+fn test_varint_fixint_mode_is_native_width() {
+    let mut writer = crate::ser::VecWriter::new();
+    VarInt(7).encode_with(&mut writer, IntEncoding::Fixint, Endian::Little).unwrap();
+    assert_eq!(writer.buf.len(), 8);
+
+    let mut reader = crate::ser::SliceReader::new(&writer.buf);
+    let decoded = VarInt::decode_with(&mut reader, IntEncoding::Fixint, Endian::Little).unwrap();
+    assert_eq!(decoded, VarInt(7));
+}
+
+#[test]
+fn test_varint_readable_through_trait_object() {
+    let mut writer = crate::ser::VecWriter::new();
+    VarInt(300).encode(&mut writer).unwrap();
+
+    let mut reader = crate::ser::SliceReader::new(&writer.buf);
+    let dyn_reader: &mut dyn Reader = &mut reader;
+    assert_eq!(VarInt::read(dyn_reader).unwrap(), VarInt(300));
+}
+
+#[test]
+fn test_varint_rejects_non_canonical_marker() {
+    // Marker 252 (u32 width) encoding a value that would have fit in the
+    // single-byte form is non-canonical and must be rejected.
+    let mut writer = crate::ser::VecWriter::new();
+    writer.write_u8(VARINT_U32_MARKER).unwrap();
+    write_width(&mut writer, 10, 4, Endian::Little).unwrap();
+
+    let mut reader = crate::ser::SliceReader::new(&writer.buf);
+    assert!(read_varint(&mut reader, Endian::Little).is_err());
+}